@@ -0,0 +1,30 @@
+use std::time::Instant;
+
+/// A free-running monotonic counter, captured once at acquisition start and read back as an
+/// elapsed offset — the same single-counter-scaled-to-us/ms approach used to timestamp
+/// acquisition samples on embedded hosts.
+pub struct Clock {
+    epoch: Instant,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+        }
+    }
+
+    pub fn get_us(&self) -> u64 {
+        self.epoch.elapsed().as_micros() as u64
+    }
+
+    pub fn get_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
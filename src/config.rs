@@ -0,0 +1,379 @@
+use ascii::AsciiChar;
+
+/// Board-level channel count / daisy mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardMode {
+    Cyton8,
+    CytonDaisy16,
+}
+
+impl BoardMode {
+    fn channel_count(self) -> usize {
+        match self {
+            BoardMode::Cyton8 => 8,
+            BoardMode::CytonDaisy16 => 16,
+        }
+    }
+
+    fn command(self) -> AsciiChar {
+        match self {
+            BoardMode::Cyton8 => AsciiChar::from_ascii('c').unwrap(),
+            BoardMode::CytonDaisy16 => AsciiChar::from_ascii('C').unwrap(),
+        }
+    }
+
+    fn expect(self) -> &'static str {
+        match self {
+            BoardMode::Cyton8 => "Channel set for 8$$$",
+            BoardMode::CytonDaisy16 => "Channel set for 16$$$",
+        }
+    }
+}
+
+/// PGA gain applied to a channel, matching the ADS1299 `x` command's gain nibble.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gain {
+    X1,
+    X2,
+    X4,
+    X6,
+    X8,
+    X12,
+    X24,
+}
+
+impl Gain {
+    fn code(self) -> AsciiChar {
+        let c = match self {
+            Gain::X1 => '0',
+            Gain::X2 => '1',
+            Gain::X4 => '2',
+            Gain::X6 => '3',
+            Gain::X8 => '4',
+            Gain::X12 => '5',
+            Gain::X24 => '6',
+        };
+        AsciiChar::from_ascii(c).unwrap()
+    }
+
+    fn from_code(code: char) -> Option<Self> {
+        match code {
+            '0' => Some(Gain::X1),
+            '1' => Some(Gain::X2),
+            '2' => Some(Gain::X4),
+            '3' => Some(Gain::X6),
+            '4' => Some(Gain::X8),
+            '5' => Some(Gain::X12),
+            '6' => Some(Gain::X24),
+            _ => None,
+        }
+    }
+
+    /// Numeric form of [`Gain::code`], for storing in a recording header.
+    pub(crate) fn index(self) -> u8 {
+        self.code().as_byte() - b'0'
+    }
+}
+
+/// ADS1299 channel input multiplexer setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdsInput {
+    Normal,
+    Shorted,
+    BiasMeas,
+    Mvdd,
+    Temp,
+    Testsig,
+    BiasDrp,
+    BiasDrn,
+}
+
+impl AdsInput {
+    fn code(self) -> AsciiChar {
+        let c = match self {
+            AdsInput::Normal => '0',
+            AdsInput::Shorted => '1',
+            AdsInput::BiasMeas => '2',
+            AdsInput::Mvdd => '3',
+            AdsInput::Temp => '4',
+            AdsInput::Testsig => '5',
+            AdsInput::BiasDrp => '6',
+            AdsInput::BiasDrn => '7',
+        };
+        AsciiChar::from_ascii(c).unwrap()
+    }
+
+    fn from_code(code: char) -> Option<Self> {
+        match code {
+            '0' => Some(AdsInput::Normal),
+            '1' => Some(AdsInput::Shorted),
+            '2' => Some(AdsInput::BiasMeas),
+            '3' => Some(AdsInput::Mvdd),
+            '4' => Some(AdsInput::Temp),
+            '5' => Some(AdsInput::Testsig),
+            '6' => Some(AdsInput::BiasDrp),
+            '7' => Some(AdsInput::BiasDrn),
+            _ => None,
+        }
+    }
+}
+
+/// Per-channel settings sent via the OpenBCI `x<channel>...X` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelConfig {
+    pub enabled: bool,
+    pub gain: Gain,
+    pub input: AdsInput,
+    pub include_bias: bool,
+    pub srb2: bool,
+    pub srb1: bool,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            gain: Gain::X24,
+            input: AdsInput::Normal,
+            include_bias: true,
+            srb2: true,
+            srb1: false,
+        }
+    }
+}
+
+impl ChannelConfig {
+    fn select_char(index: usize) -> AsciiChar {
+        let c = match index {
+            0 => '1',
+            1 => '2',
+            2 => '3',
+            3 => '4',
+            4 => '5',
+            5 => '6',
+            6 => '7',
+            7 => '8',
+            8 => 'Q',
+            9 => 'W',
+            10 => 'E',
+            11 => 'R',
+            12 => 'T',
+            13 => 'Y',
+            14 => 'U',
+            15 => 'I',
+            _ => panic!("Channel index out of range: {}", index),
+        };
+        AsciiChar::from_ascii(c).unwrap()
+    }
+
+    /// Synthesize the `x<channel><settings>X` command bytes for this channel.
+    fn command_bytes(&self, index: usize) -> Vec<u8> {
+        let on_off = if self.enabled { '0' } else { '1' };
+        let bias = if self.include_bias { '1' } else { '0' };
+        let srb2 = if self.srb2 { '1' } else { '0' };
+        let srb1 = if self.srb1 { '1' } else { '0' };
+
+        vec![
+            AsciiChar::from_ascii('x').unwrap().as_byte(),
+            Self::select_char(index).as_byte(),
+            AsciiChar::from_ascii(on_off).unwrap().as_byte(),
+            self.gain.code().as_byte(),
+            self.input.code().as_byte(),
+            AsciiChar::from_ascii(bias).unwrap().as_byte(),
+            AsciiChar::from_ascii(srb2).unwrap().as_byte(),
+            AsciiChar::from_ascii(srb1).unwrap().as_byte(),
+            AsciiChar::from_ascii('X').unwrap().as_byte(),
+        ]
+    }
+}
+
+/// A single `(command bytes, expected echo)` pair `setup()` writes and validates.
+pub(crate) struct Command {
+    pub bytes: Vec<u8>,
+    pub expect: String,
+}
+
+/// Board and per-channel configuration, synthesized into the command stream `setup()` writes.
+#[derive(Debug, Clone)]
+pub struct BoardConfig {
+    mode: BoardMode,
+    sample_rate: u32,
+    channels: Vec<ChannelConfig>,
+}
+
+impl BoardConfig {
+    pub fn builder() -> BoardConfigBuilder {
+        BoardConfigBuilder::new()
+    }
+
+    pub fn mode(&self) -> BoardMode {
+        self.mode
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn channel(&self, index: usize) -> ChannelConfig {
+        self.channels[index]
+    }
+
+    /// Read back a single config value, e.g. `"ch1.gain"` or `"sample_rate"`.
+    pub fn get_config(&self, key: &str) -> Option<String> {
+        if key == "sample_rate" {
+            return Some(self.sample_rate.to_string());
+        }
+
+        let (index, field) = Self::parse_channel_key(key)?;
+        let channel = self.channels.get(index)?;
+        Some(match field {
+            "enabled" => channel.enabled.to_string(),
+            "gain" => channel.gain.code().as_char().to_string(),
+            "input" => channel.input.code().as_char().to_string(),
+            "bias" => channel.include_bias.to_string(),
+            "srb2" => channel.srb2.to_string(),
+            "srb1" => channel.srb1.to_string(),
+            _ => return None,
+        })
+    }
+
+    /// Overwrite a single config value in place, e.g. `set_config("ch3.gain", "6")` to set
+    /// channel 3 to `Gain::X24`. `gain` and `input` take the ADS1299 command's single-digit
+    /// code (see [`Gain::from_code`]/[`AdsInput::from_code`]), not a human gain value.
+    pub fn set_config(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if key == "sample_rate" {
+            self.sample_rate = value
+                .parse()
+                .map_err(|_| format!("Invalid sample_rate value: {}", value))?;
+            return Ok(());
+        }
+
+        let (index, field) = Self::parse_channel_key(key)
+            .ok_or_else(|| format!("Unrecognized config key: {}", key))?;
+        let channel = self
+            .channels
+            .get_mut(index)
+            .ok_or_else(|| format!("Channel index out of range: {}", index))?;
+
+        match field {
+            "enabled" => channel.enabled = Self::parse_bool(value)?,
+            "gain" => {
+                let mut chars = value.chars();
+                let code = chars.next().filter(|_| chars.next().is_none());
+                channel.gain = code
+                    .and_then(Gain::from_code)
+                    .ok_or_else(|| format!("Invalid gain value: {}", value))?
+            }
+            "input" => {
+                let mut chars = value.chars();
+                let code = chars.next().filter(|_| chars.next().is_none());
+                channel.input = code
+                    .and_then(AdsInput::from_code)
+                    .ok_or_else(|| format!("Invalid input value: {}", value))?
+            }
+            "bias" => channel.include_bias = Self::parse_bool(value)?,
+            "srb2" => channel.srb2 = Self::parse_bool(value)?,
+            "srb1" => channel.srb1 = Self::parse_bool(value)?,
+            _ => return Err(format!("Unrecognized config key: {}", key)),
+        }
+
+        Ok(())
+    }
+
+    fn parse_bool(value: &str) -> Result<bool, String> {
+        match value {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(format!("Invalid boolean value: {}", value)),
+        }
+    }
+
+    fn parse_channel_key(key: &str) -> Option<(usize, &str)> {
+        let rest = key.strip_prefix("ch")?;
+        let (index, field) = rest.split_once('.')?;
+        let index: usize = index.parse().ok()?;
+        Some((index.checked_sub(1)?, field))
+    }
+
+    /// Commands `setup()` needs to write, in order: the mode/reset commands followed by the
+    /// single combined per-channel settings command.
+    pub(crate) fn commands(&self) -> Vec<Command> {
+        let mut commands = vec![
+            Command {
+                bytes: vec![AsciiChar::from_ascii('v').unwrap().as_byte(), 0x0A],
+                expect: String::from("Firmware: v3.1.2"),
+            },
+            Command {
+                bytes: vec![self.mode.command().as_byte(), 0x0A],
+                expect: format!("{}$$$", self.channels.len()),
+            },
+        ];
+
+        let mut channel_write = vec![];
+        for (index, channel) in self.channels.iter().enumerate() {
+            channel_write.extend(channel.command_bytes(index));
+        }
+        channel_write.push(0x0A);
+
+        commands.push(Command {
+            bytes: channel_write,
+            expect: self.mode.expect().to_string(),
+        });
+
+        commands
+    }
+}
+
+/// Builds a [`BoardConfig`], defaulting every channel to the board's previous hardcoded
+/// settings (on, Gain 24, normal input, bias included, SRB2 connected, SRB1 disconnected).
+pub struct BoardConfigBuilder {
+    mode: BoardMode,
+    sample_rate: u32,
+    channels: Vec<ChannelConfig>,
+}
+
+impl BoardConfigBuilder {
+    pub fn new() -> Self {
+        let mode = BoardMode::CytonDaisy16;
+        Self {
+            mode,
+            sample_rate: 250,
+            channels: vec![ChannelConfig::default(); mode.channel_count()],
+        }
+    }
+
+    pub fn mode(mut self, mode: BoardMode) -> Self {
+        self.channels.resize(mode.channel_count(), ChannelConfig::default());
+        self.mode = mode;
+        self
+    }
+
+    pub fn sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// `index` is zero-based; must be within the board mode's channel count.
+    pub fn channel(mut self, index: usize, config: ChannelConfig) -> Self {
+        self.channels[index] = config;
+        self
+    }
+
+    pub fn build(self) -> BoardConfig {
+        BoardConfig {
+            mode: self.mode,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        }
+    }
+}
+
+impl Default for BoardConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
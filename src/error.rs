@@ -0,0 +1,35 @@
+use std::fmt;
+
+/// Errors surfaced from the acquisition thread in place of panicking, delivered inline on
+/// the `Result<Reading, OpenBCIError>` channel returned by `OpenBCI::start()`. The thread sends
+/// `SerialIo` and `DroppedPacket`; read timeouts and frame resyncs are only logged to the
+/// [`crate::BufferLogger`], not sent.
+#[derive(Debug)]
+pub enum OpenBCIError {
+    /// The serial port failed to read or write.
+    SerialIo(String),
+    /// A serial read timed out waiting for data.
+    Timeout,
+    /// Frame sync was lost and the reader had to resync on a later start byte.
+    Framing(String),
+    /// A packet was received out of order and had to be discarded.
+    DroppedPacket(u8),
+    /// The reading channel's receiver was dropped.
+    ChannelClosed,
+}
+
+impl fmt::Display for OpenBCIError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenBCIError::SerialIo(message) => write!(f, "serial IO error: {}", message),
+            OpenBCIError::Timeout => write!(f, "serial read timed out"),
+            OpenBCIError::Framing(message) => write!(f, "frame sync error: {}", message),
+            OpenBCIError::DroppedPacket(sample_number) => {
+                write!(f, "dropped out-of-order packet (sample {})", sample_number)
+            }
+            OpenBCIError::ChannelClosed => write!(f, "reading channel receiver was dropped"),
+        }
+    }
+}
+
+impl std::error::Error for OpenBCIError {}
@@ -0,0 +1,58 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+static LOGGER: OnceLock<BufferLogger> = OnceLock::new();
+
+/// A fixed-capacity in-memory log sink: retains only the most recent `capacity` formatted
+/// log lines, so the acquisition thread can report resync events, dropped samples, and read
+/// timeouts without flooding stdout. A UI can drain it on demand to show recent warnings.
+pub struct BufferLogger {
+    capacity: usize,
+    buffer: Mutex<VecDeque<String>>,
+}
+
+impl BufferLogger {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            buffer: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Install a process-wide `BufferLogger` as the `log` crate's global logger and return a
+    /// handle to it. Safe to call more than once; later calls just return the first logger
+    /// installed, regardless of the capacity they request.
+    pub(crate) fn install(capacity: usize) -> &'static BufferLogger {
+        let logger = LOGGER.get_or_init(|| BufferLogger::new(capacity));
+        let _ = log::set_logger(logger).map(|()| log::set_max_level(LevelFilter::Trace));
+        logger
+    }
+
+    /// Drain and return everything currently buffered, oldest first.
+    pub fn drain(&self) -> Vec<String> {
+        let mut buffer = self.buffer.lock().unwrap();
+        buffer.drain(..).collect()
+    }
+
+    fn push(&self, line: String) {
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() == self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(line);
+    }
+}
+
+impl Log for BufferLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.push(format!("[{}] {}", record.level(), record.args()));
+    }
+
+    fn flush(&self) {}
+}
@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+
+/// Total size of a standard OpenBCI packet: start byte, sample number, 8 24-bit channels,
+/// 6 aux bytes, and a stop byte.
+pub(crate) const PACKET_SIZE: usize = 33;
+
+/// Decoded contents of a packet's 6 auxiliary bytes, determined by the stop byte's low nibble.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub enum AuxData {
+    /// Stop byte `0xC0`: the aux bytes are accelerometer X/Y/Z.
+    Accelerometer { x: u16, y: u16, z: u16 },
+    /// Stop byte `0xC1`: one accelerometer axis sample (cycled across packets by the board)
+    /// plus a board timestamp.
+    TimestampedAccel { accel_sample: i16, timestamp: u32 },
+    /// Stop byte `0xC2`: a board timestamp with no accelerometer data.
+    Timestamped { timestamp: u32 },
+}
+
+impl AuxData {
+    /// Decode the 6 aux bytes according to the stop byte that terminated the packet.
+    /// `stop_byte` must be in `0xC0..=0xCF`.
+    pub(crate) fn from_bytes(stop_byte: u8, bytes: &[u8; 6]) -> Self {
+        match stop_byte & 0x0F {
+            1 => AuxData::TimestampedAccel {
+                accel_sample: i16::from_be_bytes([bytes[0], bytes[1]]),
+                timestamp: u32::from_be_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]),
+            },
+            2 => AuxData::Timestamped {
+                timestamp: u32::from_be_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]),
+            },
+            _ => AuxData::Accelerometer {
+                x: bytes[0] as u16 | ((bytes[1] as u16) << 8),
+                y: bytes[2] as u16 | ((bytes[3] as u16) << 8),
+                z: bytes[4] as u16 | ((bytes[5] as u16) << 8),
+            },
+        }
+    }
+
+    /// Encode back to a `(stop byte, 6 aux bytes)` pair, the inverse of `from_bytes`. Used by
+    /// the recorder to store aux data losslessly.
+    pub(crate) fn to_bytes(self) -> (u8, [u8; 6]) {
+        match self {
+            AuxData::Accelerometer { x, y, z } => (
+                0xC0,
+                [
+                    (x & 0xFF) as u8,
+                    (x >> 8) as u8,
+                    (y & 0xFF) as u8,
+                    (y >> 8) as u8,
+                    (z & 0xFF) as u8,
+                    (z >> 8) as u8,
+                ],
+            ),
+            AuxData::TimestampedAccel {
+                accel_sample,
+                timestamp,
+            } => {
+                let sample = accel_sample.to_be_bytes();
+                let time = timestamp.to_be_bytes();
+                (0xC1, [sample[0], sample[1], time[0], time[1], time[2], time[3]])
+            }
+            AuxData::Timestamped { timestamp } => {
+                let time = timestamp.to_be_bytes();
+                (0xC2, [0, 0, time[0], time[1], time[2], time[3]])
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Packet {
+    pub(crate) _header: u8,       // Byte 1: Start byte (0xA0)
+    pub(crate) sample_number: u8, // Byte 2: Sample Number
+    pub(crate) chan_1: i32,       // Bytes 3-5: Data value for EEG channel 1
+    pub(crate) chan_2: i32,       // Bytes 6-8: Data value for EEG channel 2
+    pub(crate) chan_3: i32,       // Bytes 9-11: Data value for EEG channel 3
+    pub(crate) chan_4: i32,       // Bytes 12-14: Data value for EEG channel 4
+    pub(crate) chan_5: i32,       // Bytes 15-17: Data value for EEG channel 5
+    pub(crate) chan_6: i32,       // Bytes 18-20: Data value for EEG channel 6
+    pub(crate) chan_7: i32,       // Bytes 21-23: Data value for EEG channel 7
+    pub(crate) chan_8: i32,       // Bytes 24-26: Data value for EEG channel 8
+    pub(crate) aux: AuxData,      // Bytes 27-32: Aux data, decoded per the stop byte
+}
+
+impl Packet {
+    /// Decode a full 33-byte OpenBCI packet: start byte, sample number, 8 channels, 6 aux
+    /// bytes and a stop byte. The caller is responsible for locating the start/stop bytes.
+    pub(crate) fn from_bytes(bytes: &[u8; 33]) -> Self {
+        let mut aux_bytes: [u8; 6] = Default::default();
+        aux_bytes.copy_from_slice(&bytes[26..32]);
+
+        Self {
+            _header: bytes[0],
+            sample_number: bytes[1],
+            chan_1: i24toi32(&bytes[2..=4]),
+            chan_2: i24toi32(&bytes[5..=7]),
+            chan_3: i24toi32(&bytes[8..=10]),
+            chan_4: i24toi32(&bytes[11..=13]),
+            chan_5: i24toi32(&bytes[14..=16]),
+            chan_6: i24toi32(&bytes[17..=19]),
+            chan_7: i24toi32(&bytes[20..=22]),
+            chan_8: i24toi32(&bytes[23..=25]),
+            aux: AuxData::from_bytes(bytes[32], &aux_bytes),
+        }
+    }
+
+    /// This packet's 8 EEG channel values, in board order.
+    pub(crate) fn channels(&self) -> Vec<i32> {
+        vec![
+            self.chan_1,
+            self.chan_2,
+            self.chan_3,
+            self.chan_4,
+            self.chan_5,
+            self.chan_6,
+            self.chan_7,
+            self.chan_8,
+        ]
+    }
+}
+
+/// Result of scanning a read buffer for complete packets.
+pub(crate) struct FrameResult {
+    /// Packets decoded from valid `0xA0 ... stop byte` frames, in order.
+    pub(crate) packets: Vec<Packet>,
+    /// Number of leading bytes of the scanned buffer that are fully consumed (valid frames
+    /// plus any skipped resync bytes before them) and can be drained.
+    pub(crate) purge_index: usize,
+    /// Number of candidate start bytes (`0xA0`) that were rejected because the byte
+    /// `PACKET_SIZE - 1` later wasn't a valid stop byte, i.e. frame sync was lost and had to be
+    /// recovered by advancing one byte at a time.
+    pub(crate) resynced: usize,
+}
+
+/// Scan `buffer` for `0xA0 ... stop byte` frames, decoding each one found. A candidate start
+/// byte is only accepted when the byte `PACKET_SIZE - 1` later is a valid stop byte
+/// (`0xC0..=0xCF`); otherwise sync is lost and the scan advances one byte at a time until it
+/// finds a real frame boundary again.
+pub(crate) fn frame_packets(buffer: &[u8]) -> FrameResult {
+    let mut packets = vec![];
+    let mut purge_index = 0;
+    let mut resynced = 0;
+
+    let mut index = 0;
+    while index + PACKET_SIZE <= buffer.len() {
+        let stop_byte = buffer[index + PACKET_SIZE - 1];
+        if buffer[index] == 0xA0 && (0xC0..=0xCF).contains(&stop_byte) {
+            let mut data: [u8; PACKET_SIZE] = [0; PACKET_SIZE];
+            data.copy_from_slice(&buffer[index..index + PACKET_SIZE]);
+            packets.push(Packet::from_bytes(&data));
+            purge_index = index + PACKET_SIZE;
+            index += PACKET_SIZE;
+        } else {
+            if buffer[index] == 0xA0 {
+                resynced += 1;
+            }
+            index += 1;
+        }
+    }
+
+    FrameResult {
+        packets,
+        purge_index,
+        resynced,
+    }
+}
+
+pub(crate) fn i24toi32(bytes: &[u8]) -> i32 {
+    if bytes.len() != 3 {
+        panic!("Byte array isn't of length 3");
+    }
+    let mut result: i32 = ((0xFF & bytes[0] as i32) << 16)
+        | ((0xFF & bytes[1] as i32) << 8)
+        | (0xFF & bytes[2] as i32);
+    if (result & 0x00800000) > 0 {
+        result = -(result & 0x007FFFFF);
+    } else {
+        result &= 0x00FFFFFF;
+    }
+
+    result
+}
+
+/// Pack a channel value back into the board's 3-byte, sign-and-magnitude 24-bit layout
+/// (the inverse of `i24toi32`), for the recorder's on-disk frame format.
+pub(crate) fn i32_to_i24(value: i32) -> [u8; 3] {
+    let raw = if value < 0 {
+        0x00800000 | ((-value) & 0x007FFFFF)
+    } else {
+        value & 0x007FFFFF
+    };
+    [
+        ((raw >> 16) & 0xFF) as u8,
+        ((raw >> 8) & 0xFF) as u8,
+        (raw & 0xFF) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_small_i24toi32() {
+        let positive_array: [u8; 3] = [0x07, 0x86, 0x9E];
+        let positive_value = 493214;
+        let result = i24toi32(&positive_array);
+        assert_eq!(positive_value, result);
+
+        let negative_array: [u8; 3] = [0x87, 0x86, 0x9E];
+        let negative_value = -493214;
+        let result = i24toi32(&negative_array);
+        assert_eq!(negative_value, result);
+    }
+
+    #[test]
+    fn test_large_i24toi32() {
+        let positive_array: [u8; 3] = [0x7F, 0xFF, 0xFF];
+        let positive_value = 8388607;
+        let result = i24toi32(&positive_array);
+        assert_eq!(positive_value, result);
+
+        let negative_array: [u8; 3] = [0xFF, 0xFF, 0xFF];
+        let negative_value = -8388607;
+        let result = i24toi32(&negative_array);
+        assert_eq!(negative_value, result);
+    }
+
+    #[test]
+    fn test_tiny_i24toi32() {
+        let positive_array: [u8; 3] = [0x00, 0x00, 0x01];
+        let positive_value = 1;
+        let result = i24toi32(&positive_array);
+        assert_eq!(positive_value, result);
+
+        let negative_array: [u8; 3] = [0x80, 0x00, 0x01];
+        let negative_value = -1;
+        let result = i24toi32(&negative_array);
+        assert_eq!(negative_value, result);
+    }
+
+    #[test]
+    fn test_i32_to_i24_round_trips() {
+        for value in [0, 1, -1, 8388607, -8388607, 493214, -493214] {
+            let bytes = i32_to_i24(value);
+            assert_eq!(value, i24toi32(&bytes));
+        }
+    }
+
+    /// Build a well-formed 33-byte packet with the given stop byte and aux payload.
+    fn make_packet(stop_byte: u8, aux: [u8; 6]) -> [u8; PACKET_SIZE] {
+        let mut bytes = [0u8; PACKET_SIZE];
+        bytes[0] = 0xA0;
+        bytes[1] = 7; // sample number
+        bytes[26..32].copy_from_slice(&aux);
+        bytes[32] = stop_byte;
+        bytes
+    }
+
+    #[test]
+    fn test_from_bytes_dispatches_accelerometer_aux() {
+        let bytes = make_packet(0xC0, [0x01, 0x00, 0x02, 0x00, 0x03, 0x00]);
+        let packet = Packet::from_bytes(&bytes);
+        assert_eq!(packet.sample_number, 7);
+        match packet.aux {
+            AuxData::Accelerometer { x, y, z } => assert_eq!((x, y, z), (1, 2, 3)),
+            other => panic!("expected Accelerometer aux, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_dispatches_timestamped_accel_aux() {
+        let bytes = make_packet(0xC1, [0x00, 0x05, 0x00, 0x00, 0x00, 0x64]);
+        let packet = Packet::from_bytes(&bytes);
+        match packet.aux {
+            AuxData::TimestampedAccel {
+                accel_sample,
+                timestamp,
+            } => {
+                assert_eq!(accel_sample, 5);
+                assert_eq!(timestamp, 100);
+            }
+            other => panic!("expected TimestampedAccel aux, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_dispatches_timestamped_aux() {
+        let bytes = make_packet(0xC2, [0x00, 0x00, 0x00, 0x00, 0x00, 0x0A]);
+        let packet = Packet::from_bytes(&bytes);
+        match packet.aux {
+            AuxData::Timestamped { timestamp } => assert_eq!(timestamp, 10),
+            other => panic!("expected Timestamped aux, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_frame_packets_finds_aligned_packet() {
+        let bytes = make_packet(0xC0, [0; 6]);
+        let result = frame_packets(&bytes);
+        assert_eq!(result.packets.len(), 1);
+        assert_eq!(result.purge_index, PACKET_SIZE);
+        assert_eq!(result.resynced, 0);
+    }
+
+    #[test]
+    fn test_frame_packets_resyncs_past_corrupted_leading_bytes() {
+        // Garbage containing a false 0xA0 start candidate, followed by a real packet.
+        let mut buffer = vec![0xFFu8, 0xA0, 0x00];
+        buffer.extend(make_packet(0xC0, [0; 6]));
+
+        let result = frame_packets(&buffer);
+        assert_eq!(result.packets.len(), 1);
+        assert_eq!(result.packets[0].sample_number, 7);
+        // The false 0xA0 at index 1 isn't followed by a valid stop byte PACKET_SIZE - 1 later,
+        // so it must be rejected and skipped during resync.
+        assert_eq!(result.resynced, 1);
+        assert_eq!(result.purge_index, buffer.len());
+    }
+
+    #[test]
+    fn test_frame_packets_rejects_invalid_stop_byte() {
+        let mut bytes = make_packet(0xFF, [0; 6]);
+        bytes[32] = 0xFF;
+        let result = frame_packets(&bytes);
+        assert!(result.packets.is_empty());
+        assert_eq!(result.purge_index, 0);
+    }
+}
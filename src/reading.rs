@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::packet::{AuxData, Packet};
+
+/// A single timepoint of EEG data. `channels` holds one value per board channel, enabled or not:
+/// 8 entries in Cyton mode, 16 in Cyton+Daisy mode, matching `BoardConfig::channel_count()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reading {
+    /// The board's own sample number(s): a single value in 8-channel mode, or the
+    /// `(odd, even)` pair of board and daisy sample numbers in 16-channel mode.
+    pub sample_numbers: (u8, Option<u8>),
+    pub channels: Vec<i32>,
+    pub acc_x: u16,
+    pub acc_y: u16,
+    pub acc_z: u16,
+    pub aux: AuxData,
+    /// Host receive time, in microseconds since acquisition start.
+    pub timestamp_us: u64,
+    /// Samples dropped since the previous `Reading`, detected from a gap in the board's
+    /// (mod-256) sample numbers.
+    pub dropped_samples: u8,
+}
+
+impl Reading {
+    /// Build a `Reading` from a single packet (8-channel Cyton mode, no daisy pairing).
+    pub(crate) fn from_packet(packet: Packet, timestamp_us: u64, dropped_samples: u8) -> Self {
+        let (acc_x, acc_y, acc_z) = Self::accelerometer(packet.aux);
+
+        Self {
+            sample_numbers: (packet.sample_number, None),
+            channels: packet.channels(),
+            acc_x,
+            acc_y,
+            acc_z,
+            aux: packet.aux,
+            timestamp_us,
+            dropped_samples,
+        }
+    }
+
+    /// Build a `Reading` from the odd/even packet pair produced by 16-channel daisy mode.
+    pub(crate) fn from_packets(
+        packets: [Packet; 2],
+        timestamp_us: u64,
+        dropped_samples: u8,
+    ) -> Self {
+        let (acc_x, acc_y, acc_z) = Self::accelerometer(packets[1].aux);
+
+        let mut channels = packets[0].channels();
+        channels.extend(packets[1].channels());
+
+        Self {
+            sample_numbers: (packets[0].sample_number, Some(packets[1].sample_number)),
+            channels,
+            acc_x,
+            acc_y,
+            acc_z,
+            aux: packets[1].aux,
+            timestamp_us,
+            dropped_samples,
+        }
+    }
+
+    /// Number of samples dropped between two consecutive board sample numbers, given the
+    /// expected per-`Reading` step (1 in 8-channel mode, 2 in 16-channel daisy mode). Wraps
+    /// correctly across the sample number's mod-256 rollover.
+    pub(crate) fn dropped_since(previous: u8, current: u8, step: u8) -> u8 {
+        let gap = current.wrapping_sub(previous);
+        gap.saturating_sub(step)
+    }
+
+    pub(crate) fn accelerometer(aux: AuxData) -> (u16, u16, u16) {
+        match aux {
+            AuxData::Accelerometer { x, y, z } => (x, y, z),
+            _ => (0, 0, 0),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::config::BoardConfig;
+use crate::packet::{i24toi32, i32_to_i24, AuxData};
+use crate::reading::Reading;
+
+const MAGIC: &[u8; 4] = b"ROBC";
+const FORMAT_VERSION: u8 = 1;
+
+/// Writes `Reading`s to a compact binary format instead of one `serde` blob per sample: a
+/// small header (channel count, sample rate, per-channel gains from `BoardConfig`) followed
+/// by tightly packed frames where each 24-bit channel value is stored as 3 bytes, matching
+/// the wire size, instead of a 4-byte `i32`.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    channel_count: usize,
+}
+
+impl Recorder {
+    pub fn open<P: AsRef<Path>>(path: P, config: &BoardConfig) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        let channel_count = config.channel_count();
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[FORMAT_VERSION])?;
+        writer.write_all(&[channel_count as u8])?;
+        writer.write_all(&config.sample_rate().to_be_bytes())?;
+        for index in 0..channel_count {
+            writer.write_all(&[config.channel(index).gain.index()])?;
+        }
+
+        Ok(Self {
+            writer,
+            channel_count,
+        })
+    }
+
+    /// Append one packed frame for `reading`.
+    pub fn record(&mut self, reading: &Reading) -> io::Result<()> {
+        if reading.channels.len() != self.channel_count {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "reading has {} channels, recorder was opened with {}",
+                    reading.channels.len(),
+                    self.channel_count
+                ),
+            ));
+        }
+
+        self.writer.write_all(&[reading.sample_numbers.0])?;
+        match reading.sample_numbers.1 {
+            Some(secondary) => self.writer.write_all(&[1, secondary])?,
+            None => self.writer.write_all(&[0, 0])?,
+        }
+        self.writer.write_all(&reading.timestamp_us.to_be_bytes())?;
+        self.writer.write_all(&[reading.dropped_samples])?;
+
+        let (aux_tag, aux_bytes) = reading.aux.to_bytes();
+        self.writer.write_all(&[aux_tag])?;
+        self.writer.write_all(&aux_bytes)?;
+
+        for channel in &reading.channels {
+            self.writer.write_all(&i32_to_i24(*channel))?;
+        }
+
+        Ok(())
+    }
+
+    /// Flush any buffered frames to disk.
+    pub fn finalize(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Streams `Reading`s back out of a file written by [`Recorder`].
+pub struct RecordingReader {
+    reader: BufReader<File>,
+    channel_count: usize,
+    sample_rate: u32,
+}
+
+impl RecordingReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a ropenbci recording",
+            ));
+        }
+
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported recording format version {}", version[0]),
+            ));
+        }
+
+        let mut channel_count = [0u8; 1];
+        reader.read_exact(&mut channel_count)?;
+        let channel_count = channel_count[0] as usize;
+
+        let mut sample_rate = [0u8; 4];
+        reader.read_exact(&mut sample_rate)?;
+        let sample_rate = u32::from_be_bytes(sample_rate);
+
+        let mut gains = vec![0u8; channel_count];
+        reader.read_exact(&mut gains)?;
+
+        Ok(Self {
+            reader,
+            channel_count,
+            sample_rate,
+        })
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channel_count
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = io::Result<Reading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut primary = [0u8; 1];
+        match self.reader.read_exact(&mut primary) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return None,
+            Err(e) => return Some(Err(e)),
+        }
+
+        let read_rest = |reader: &mut BufReader<File>, channel_count: usize| -> io::Result<Reading> {
+            let mut secondary_flag = [0u8; 2];
+            reader.read_exact(&mut secondary_flag)?;
+            let secondary = if secondary_flag[0] == 1 {
+                Some(secondary_flag[1])
+            } else {
+                None
+            };
+
+            let mut timestamp_bytes = [0u8; 8];
+            reader.read_exact(&mut timestamp_bytes)?;
+            let timestamp_us = u64::from_be_bytes(timestamp_bytes);
+
+            let mut dropped_samples = [0u8; 1];
+            reader.read_exact(&mut dropped_samples)?;
+
+            let mut aux_tag = [0u8; 1];
+            reader.read_exact(&mut aux_tag)?;
+            let mut aux_bytes = [0u8; 6];
+            reader.read_exact(&mut aux_bytes)?;
+            let aux = AuxData::from_bytes(aux_tag[0], &aux_bytes);
+            let (acc_x, acc_y, acc_z) = Reading::accelerometer(aux);
+
+            let mut channels = Vec::with_capacity(channel_count);
+            let mut channel_bytes = [0u8; 3];
+            for _ in 0..channel_count {
+                reader.read_exact(&mut channel_bytes)?;
+                channels.push(i24toi32(&channel_bytes));
+            }
+
+            Ok(Reading {
+                sample_numbers: (primary[0], secondary),
+                channels,
+                acc_x,
+                acc_y,
+                acc_z,
+                aux,
+                timestamp_us,
+                dropped_samples: dropped_samples[0],
+            })
+        };
+
+        Some(read_rest(&mut self.reader, self.channel_count))
+    }
+}